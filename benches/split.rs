@@ -0,0 +1,44 @@
+//! Compare splitting a large, `\n\n`-heavy input into blocks with a plain `str::split`.
+//! Run with `cargo bench`, and again with `cargo bench --features memchr` to see the effect of the `memchr` feature.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use textblocks::*;
+
+/// Run `f` repeatedly, returning the fastest run.
+fn fastest(mut f: impl FnMut() -> usize) -> Duration {
+    (0..20)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let feature = if cfg!(feature = "memchr") {
+        "with memchr"
+    } else {
+        "without memchr"
+    };
+    let block_delimiter = BlockDelimiter::DoubleLineGeneric;
+    // About 20 MiB of 8 line blocks, from many short lines to fewer long ones
+    for line_len in [4, 80, 400] {
+        let block = vec!["x".repeat(line_len); 8].join("\n");
+        let input = vec![block.as_str(); 20_000_000 / block.len()].join("\n\n");
+        let expected: Vec<&str> = input.split("\n\n").map(str::trim).collect();
+        assert_eq!(input.block_slices(&block_delimiter), expected);
+
+        let std_split = fastest(|| {
+            let blocks: Vec<&str> = black_box(&input).split("\n\n").map(str::trim).collect();
+            blocks.len()
+        });
+        let block_slices = fastest(|| black_box(&input).block_slices(&block_delimiter).len());
+        println!(
+            "{} blocks of {line_len} byte lines: str::split {std_split:?}, block_slices ({feature}) {block_slices:?}",
+            expected.len()
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Error types returned by the fallible methods of `TextBlocks`.
+
+use std::fmt;
+
+/// A block containing only whitespace, rejected by `EmptyBlockPolicy::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyBlock {
+    /// The index of the empty block.
+    pub index: usize,
+}
+
+impl fmt::Display for EmptyBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block {} is empty", self.index)
+    }
+}
+
+impl std::error::Error for EmptyBlock {}
+
+/// An input without any blocks, rejected by `TextBlocks::block_parse_nonempty`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmptyInput;
+
+impl fmt::Display for EmptyInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input has no blocks")
+    }
+}
+
+impl std::error::Error for EmptyInput {}
+
+/// An input that mixes several kinds of line endings, rejected by `TextBlocks::try_as_blocks_strict_endings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MixedLineEndings;
+
+impl fmt::Display for MixedLineEndings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input has mixed line endings")
+    }
+}
+
+impl std::error::Error for MixedLineEndings {}
+
+/// A block with more lines than allowed, rejected by `TextBlocks::try_block_parse_lines_capped`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTooLarge {
+    /// The index of the first block with too many lines.
+    pub index: usize,
+    /// The maximum number of lines that was allowed per block.
+    pub max_lines: usize,
+}
+
+impl fmt::Display for BlockTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {} has more than {} lines",
+            self.index, self.max_lines
+        )
+    }
+}
+
+impl std::error::Error for BlockTooLarge {}
+
+/// An input that would produce more blocks than allowed, rejected by `TextBlocks::try_as_blocks_max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyBlocks {
+    /// The maximum number of blocks that was allowed.
+    pub max_blocks: usize,
+}
+
+impl fmt::Display for TooManyBlocks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input has more than {} blocks", self.max_blocks)
+    }
+}
+
+impl std::error::Error for TooManyBlocks {}
+
+/// A line that failed to parse, as recorded in a `ParseOutcome`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError<E> {
+    /// The index of the block containing the line.
+    pub block: usize,
+    /// The index of the line within its block.
+    pub line: usize,
+    /// The error returned by the parser.
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for LineError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "block {}, line {}: {}",
+            self.block, self.line, self.error
+        )
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for LineError<E> {}
@@ -0,0 +1,9 @@
+//! A curated set of imports, for using this crate without glob-importing everything.
+//!
+//! ```rust
+//! use textblocks::prelude::*;
+//! let s = "100\n200\n\n300";
+//! assert_eq!(s.as_blocks(BlockDelimiter::DoubleLineGeneric), [vec!["100", "200"], vec!["300"]]);
+//! ```
+
+pub use crate::{BlockDelimiter, TextBlocks};
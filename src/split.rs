@@ -0,0 +1,398 @@
+//! Internal scanning routines for splitting a string into its raw blocks.
+
+/// What separates blocks, as resolved from a `BlockDelimiter`.
+#[derive(Debug, Clone)]
+pub(crate) enum Separator {
+    /// Any occurrence of a delimiter string. Create with `Separator::str`, which finds out whether
+    /// two occurrences of the delimiter can overlap (e.g. `"aa"` in `"aaa"`).
+    Str { delimiter: String, overlaps: bool },
+    /// A line consisting of exactly this string. Its line breaks are part of the separator.
+    Line(String),
+    /// A blank line, which may contain whitespace, with this line break on both sides.
+    Blank(String),
+    /// A NUL (`\0`) terminating each record. A NUL at the very end of the input terminates the last record,
+    /// instead of starting an empty one, and records are never trimmed, since whitespace is valid in them.
+    Nul,
+    /// Any non-empty match of a regex. Since a regex can't be searched backwards, the last match is found
+    /// by scanning from the start.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl Separator {
+    /// A separator on any occurrence of `delimiter`, used for custom delimiters.
+    /// After a match, scanning resumes right after the whole delimiter, so the end of a match is never re-used
+    /// as the start of the next one. For example, `"xaaaay"` split on `"aa"` gives `["x", "", "y"]`.
+    /// Back-to-back delimiters produce an empty block between them, and an empty delimiter doesn't split at all.
+    pub(crate) fn str(delimiter: impl Into<String>) -> Self {
+        let delimiter = delimiter.into();
+        let bytes = delimiter.as_bytes();
+        // Two occurrences can overlap if a proper prefix of the delimiter is also its suffix, like in "aa" or "abab"
+        let overlaps = (1..bytes.len()).any(|k| bytes[..k] == bytes[bytes.len() - k..]);
+        Separator::Str {
+            delimiter,
+            overlaps,
+        }
+    }
+
+    /// Find the first separator in `s`, returning its start and end byte offsets.
+    fn find(&self, s: &str) -> Option<(usize, usize)> {
+        match self {
+            Separator::Str { delimiter, .. } if delimiter.is_empty() => None,
+            Separator::Str { delimiter, .. } => {
+                find_str(s, delimiter).map(|start| (start, start + delimiter.len()))
+            }
+            Separator::Line(line) => {
+                find_iter(s, line).find_map(|start| line_span(s, start, start + line.len()))
+            }
+            Separator::Blank(line_break) => find_iter(s, line_break)
+                .find_map(|start| Some((start, start + blank_len(&s[start..], line_break)?))),
+            Separator::Nul => find_str(s, "\0").map(|start| (start, start + 1)),
+            // An empty match would split forever without consuming anything
+            #[cfg(feature = "regex")]
+            Separator::Regex(re) => re
+                .find_iter(s)
+                .find(|m| !m.is_empty())
+                .map(|m| (m.start(), m.end())),
+        }
+    }
+
+    /// Find the last separator in `s`, the same one that `find` would reach by scanning from the start.
+    /// This searches backwards, except for a delimiter string that overlaps itself, where the last occurrence
+    /// may not be a match when scanning from the start (e.g. `"aa"` in `"aaa"`).
+    fn find_last(&self, s: &str) -> Option<(usize, usize)> {
+        match self {
+            Separator::Str { delimiter, .. } if delimiter.is_empty() => None,
+            Separator::Str {
+                delimiter,
+                overlaps: false,
+            } => rfind_str(s, delimiter).map(|start| (start, start + delimiter.len())),
+            Separator::Str { overlaps: true, .. } => self.find_last_forward(s),
+            Separator::Line(line) => {
+                // A line has no line breaks, so no whole line match can overlap a later occurrence
+                let mut end = s.len();
+                while let Some(start) = rfind_str(&s[..end], line) {
+                    if let Some(span) = line_span(s, start, start + line.len()) {
+                        return Some(span);
+                    }
+                    end = start;
+                }
+                None
+            }
+            Separator::Blank(line_break) => find_last_blank(s, line_break),
+            Separator::Nul => rfind_str(s, "\0").map(|start| (start, start + 1)),
+            #[cfg(feature = "regex")]
+            Separator::Regex(_) => self.find_last_forward(s),
+        }
+    }
+
+    /// Find the last separator in `s` by scanning from the start.
+    fn find_last_forward(&self, s: &str) -> Option<(usize, usize)> {
+        let mut last = None;
+        let mut offset = 0;
+        while let Some((start, end)) = self.find(&s[offset..]) {
+            last = Some((offset + start, offset + end));
+            offset += end;
+        }
+        last
+    }
+
+    /// The length of the prefix of `s` that ends with its last separator, or 0 if `s` has no separator.
+    pub(crate) fn terminated_len(&self, s: &str) -> usize {
+        self.find_last(s).map_or(0, |(_, end)| end)
+    }
+
+    /// Check whether a separator starts at the given byte offset of `s`.
+    pub(crate) fn is_at(&self, s: &str, offset: usize) -> bool {
+        let Some(rest) = s.get(offset..) else {
+            return false;
+        };
+        match self {
+            Separator::Str { delimiter, .. } => rest.starts_with(delimiter.as_str()),
+            Separator::Line(line) => {
+                rest.starts_with(line.as_str())
+                    && line_span(s, offset, offset + line.len()).is_some()
+            }
+            Separator::Blank(line_break) => blank_len(rest, line_break).is_some(),
+            Separator::Nul => rest.starts_with('\0'),
+            #[cfg(feature = "regex")]
+            Separator::Regex(_) => matches!(self.find(rest), Some((0, _))),
+        }
+    }
+
+    /// Remove a separator from the end of `s`, if it ends with one.
+    pub(crate) fn strip_suffix<'a>(&self, s: &'a str) -> Option<&'a str> {
+        match self {
+            Separator::Str { delimiter, .. } => s.strip_suffix(delimiter.as_str()),
+            Separator::Line(line) => {
+                let start = s.rfind(line.as_str())?;
+                match line_span(s, start, start + line.len())? {
+                    (start, end) if end == s.len() => Some(&s[..start]),
+                    _ => None,
+                }
+            }
+            Separator::Blank(line_break) => s
+                .strip_suffix(line_break.as_str())?
+                .trim_end_matches(is_blank)
+                .strip_suffix(line_break.as_str()),
+            Separator::Nul => s.strip_suffix('\0'),
+            #[cfg(feature = "regex")]
+            Separator::Regex(_) => match self.find_last_forward(s)? {
+                (start, end) if end == s.len() => Some(&s[..start]),
+                _ => None,
+            },
+        }
+    }
+
+    /// Trim whitespace around the input or one of its blocks, except for NUL separated records,
+    /// which are taken as they are.
+    pub(crate) fn trim<'a>(&self, s: &'a str) -> &'a str {
+        match self {
+            Separator::Nul => s,
+            _ => s.trim(),
+        }
+    }
+
+    /// Split `s` into its raw (untrimmed) blocks.
+    /// A separator line at the very start of `s` (as is common in YAML streams) doesn't produce an empty first block,
+    /// and a NUL at the very end of `s` doesn't produce an empty last record.
+    pub(crate) fn split<'a>(&self, s: &'a str) -> SplitBlocks<'a> {
+        let s = match (self, self.find(s)) {
+            (Separator::Line(_), Some((0, end))) => &s[end..],
+            (Separator::Nul, _) => s.strip_suffix('\0').unwrap_or(s),
+            _ => s,
+        };
+        SplitBlocks {
+            rest: Some(s),
+            separator: self.clone(),
+        }
+    }
+
+    /// Split the input `s` into its raw (untrimmed) blocks, after trimming the input itself (see `trim`).
+    /// An input containing nothing but whitespace and separators has no blocks, so nothing is returned for it.
+    /// Every method that splits a trimmed input goes through here, so they all agree on what a block is.
+    pub(crate) fn blocks<'a>(&self, s: &'a str) -> SplitBlocks<'a> {
+        let mut blocks = self.split(self.trim(s));
+        if blocks.clone().all(|block| self.trim(block).is_empty()) {
+            blocks.rest = None;
+        }
+        blocks
+    }
+
+    /// Split the input `s` into its raw blocks (see `blocks`) and map each one, collecting the results.
+    pub(crate) fn split_map<'a, T>(&self, s: &'a str, f: impl FnMut(&'a str) -> T) -> Vec<T> {
+        self.blocks(s).map(f).collect()
+    }
+}
+
+/// Find the first occurrence of a non-empty `delimiter` in `s`.
+#[cfg(not(feature = "memchr"))]
+fn find_str(s: &str, delimiter: &str) -> Option<usize> {
+    s.find(delimiter)
+}
+
+/// Find the first occurrence of a non-empty `delimiter` in `s`, using `memchr`'s SIMD accelerated search.
+/// Since both are valid UTF-8, a byte match always starts on a `char` boundary, so results are the same as `str::find`.
+#[cfg(feature = "memchr")]
+fn find_str(s: &str, delimiter: &str) -> Option<usize> {
+    memchr::memmem::find(s.as_bytes(), delimiter.as_bytes())
+}
+
+/// Find every non-overlapping occurrence of a non-empty `delimiter` in `s`, from the start.
+#[cfg(not(feature = "memchr"))]
+fn find_iter<'a>(s: &'a str, delimiter: &'a str) -> impl Iterator<Item = usize> + 'a {
+    s.match_indices(delimiter).map(|(start, _)| start)
+}
+
+/// Find every non-overlapping occurrence of a non-empty `delimiter` in `s`, from the start,
+/// using `memchr`'s SIMD accelerated search.
+#[cfg(feature = "memchr")]
+fn find_iter<'a>(s: &'a str, delimiter: &'a str) -> impl Iterator<Item = usize> + 'a {
+    memchr::memmem::find_iter(s.as_bytes(), delimiter.as_bytes())
+}
+
+/// Find the last occurrence of a non-empty `delimiter` in `s`.
+#[cfg(not(feature = "memchr"))]
+fn rfind_str(s: &str, delimiter: &str) -> Option<usize> {
+    s.rfind(delimiter)
+}
+
+/// Find the last occurrence of a non-empty `delimiter` in `s`, using `memchr`'s SIMD accelerated search.
+#[cfg(feature = "memchr")]
+fn rfind_str(s: &str, delimiter: &str) -> Option<usize> {
+    memchr::memmem::rfind(s.as_bytes(), delimiter.as_bytes())
+}
+
+/// Whitespace that can appear on a blank line, which excludes the line breaks themselves.
+fn is_blank(c: char) -> bool {
+    c.is_whitespace() && c != '\n' && c != '\r'
+}
+
+/// If `s` starts with a blank line separator (a line break, optional whitespace and another line break),
+/// return its length.
+fn blank_len(s: &str, line_break: &str) -> Option<usize> {
+    let after = s.strip_prefix(line_break)?;
+    let whitespace = after.len() - after.trim_start_matches(is_blank).len();
+    after[whitespace..]
+        .starts_with(line_break)
+        .then(|| 2 * line_break.len() + whitespace)
+}
+
+/// Find the last blank line separator in `s`, the same one that scanning from the start would reach.
+/// Consecutive blank lines share their line breaks, and scanning from the start pairs up the line breaks
+/// of a run of blank lines from its first one, so the last run is scanned backwards to its start.
+fn find_last_blank(s: &str, line_break: &str) -> Option<(usize, usize)> {
+    // The starts of the last (up to) three line breaks of the run, from the last one backwards
+    let mut last = [0; 3];
+    let mut count = 0;
+    let mut previous = None;
+    let mut end = s.len();
+    while let Some(start) = rfind_str(&s[..end], line_break) {
+        let blank_line =
+            previous.is_some_and(|p| s[start + line_break.len()..p].chars().all(is_blank));
+        if blank_line {
+            if count < last.len() {
+                last[count] = start;
+            }
+            count += 1;
+        } else if count >= 2 {
+            break;
+        } else {
+            last[0] = start;
+            count = 1;
+        }
+        previous = Some(start);
+        end = start;
+    }
+    // With an odd number of line breaks, the last one is left over after pairing them up
+    let (first, second) = match count {
+        0 | 1 => return None,
+        count if count % 2 == 0 => (last[1], last[0]),
+        _ => (last[2], last[1]),
+    };
+    Some((first, second + line_break.len()))
+}
+
+/// If `s[start..end]` is a whole line, return its span extended to include its line breaks.
+fn line_span(s: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let (before, after) = (&s[..start], &s[end..]);
+    let start = if before.is_empty() {
+        start
+    } else if before.ends_with("\r\n") {
+        start - 2
+    } else if before.ends_with(['\n', '\r']) {
+        start - 1
+    } else {
+        return None;
+    };
+    let end = if after.is_empty() {
+        end
+    } else if after.starts_with("\r\n") {
+        end + 2
+    } else if after.starts_with(['\n', '\r']) {
+        end + 1
+    } else {
+        return None;
+    };
+    Some((start, end))
+}
+
+/// An iterator over the raw (untrimmed) blocks of a string, created by `Separator::split`.
+/// Blocks can be taken from both ends, and are the same either way.
+#[derive(Clone)]
+pub(crate) struct SplitBlocks<'a> {
+    rest: Option<&'a str>,
+    separator: Separator,
+}
+
+impl SplitBlocks<'_> {
+    /// The separator between the blocks.
+    pub(crate) fn separator(&self) -> &Separator {
+        &self.separator
+    }
+}
+
+impl<'a> Iterator for SplitBlocks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.take()?;
+        match self.separator.find(rest) {
+            Some((start, end)) => {
+                self.rest = Some(&rest[end..]);
+                Some(&rest[..start])
+            }
+            None => Some(rest),
+        }
+    }
+}
+
+impl DoubleEndedIterator for SplitBlocks<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let rest = self.rest?;
+        match self.separator.find_last(rest) {
+            Some((start, end)) => {
+                self.rest = Some(&rest[..start]);
+                Some(&rest[end..])
+            }
+            None => self.rest.take(),
+        }
+    }
+}
+
+/// Split a block into its lines, on every occurrence of `line_delimiter`.
+/// If `quoted` is set, line delimiters between double quotes don't split, so a quoted field can span several lines,
+/// like in CSV (RFC 4180). An escaped quote (`""`) toggles the quoting twice, so it doesn't need special handling.
+pub(crate) fn split_lines<'a, 'd>(
+    s: &'a str,
+    line_delimiter: &'d str,
+    quoted: bool,
+) -> SplitLines<'a, 'd> {
+    SplitLines {
+        rest: Some(s),
+        line_delimiter,
+        quoted,
+    }
+}
+
+/// An iterator over the lines of a block, created by `split_lines`.
+pub(crate) struct SplitLines<'a, 'd> {
+    rest: Option<&'a str>,
+    line_delimiter: &'d str,
+    quoted: bool,
+}
+
+impl SplitLines<'_, '_> {
+    /// Find the first line delimiter in `s` that isn't between double quotes.
+    fn find_unquoted(&self, s: &str) -> Option<usize> {
+        let mut in_quotes = false;
+        for (i, c) in s.char_indices() {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            } else if !in_quotes && s[i..].starts_with(self.line_delimiter) {
+                return Some(i);
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Iterator for SplitLines<'a, '_> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.take()?;
+        let end = if self.quoted {
+            self.find_unquoted(rest)
+        } else {
+            rest.find(self.line_delimiter)
+        };
+        match end {
+            Some(end) => {
+                self.rest = Some(&rest[end + self.line_delimiter.len()..]);
+                Some(&rest[..end])
+            }
+            None => Some(rest),
+        }
+    }
+}